@@ -0,0 +1,41 @@
+mod wayland;
+mod x11;
+
+pub(crate) use wayland::WaylandBackend;
+pub(crate) use x11::{watch as watch_x11, X11Backend};
+
+use crate::monitor::Monitor;
+
+/// Source of truth for the monitors currently attached to the session. `X11Backend` talks to
+/// RandR over XCB; `WaylandBackend` talks to a wlr-output-management-capable compositor.
+pub(crate) trait Backend {
+    fn monitors(&self) -> Result<Vec<Monitor>, anyhow::Error>;
+}
+
+/// Which `Backend` to use. Selected explicitly via `--backend`, or autodetected from the
+/// session's environment when not given.
+#[derive(Copy, Clone, Debug, clap::ValueEnum)]
+#[value(rename_all = "lowercase")]
+pub(crate) enum BackendKind {
+    X11,
+    Wayland,
+}
+
+impl BackendKind {
+    /// Picks Wayland when `WAYLAND_DISPLAY` is set, falling back to X11 otherwise. Mirrors how
+    /// most desktop toolkits choose a display backend when not told explicitly.
+    pub(crate) fn detect() -> Self {
+        if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+            BackendKind::Wayland
+        } else {
+            BackendKind::X11
+        }
+    }
+
+    pub(crate) fn build(self) -> Box<dyn Backend> {
+        match self {
+            BackendKind::X11 => Box::new(X11Backend),
+            BackendKind::Wayland => Box::new(WaylandBackend),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use wayland_client::backend::ObjectData;
+use wayland_client::globals::{registry_queue_init, GlobalListContents};
+use wayland_client::protocol::wl_registry;
+use wayland_client::{Connection, Dispatch, QueueHandle};
+use wayland_protocols_wlr::output_management::v1::client::zwlr_output_head_v1::{
+    self, ZwlrOutputHeadV1,
+};
+use wayland_protocols_wlr::output_management::v1::client::zwlr_output_manager_v1::{
+    self, ZwlrOutputManagerV1,
+};
+use wayland_protocols_wlr::output_management::v1::client::zwlr_output_mode_v1::{
+    self, ZwlrOutputModeV1,
+};
+
+use super::Backend;
+use crate::monitor::Monitor;
+
+/// Enumerates monitors via the `wlr-output-management-unstable-v1` protocol, as implemented by
+/// wlroots-based compositors (sway, river, Hyprland, ...).
+pub(crate) struct WaylandBackend;
+
+impl Backend for WaylandBackend {
+    fn monitors(&self) -> Result<Vec<Monitor>, anyhow::Error> {
+        let conn = Connection::connect_to_env()?;
+        let (globals, mut queue) = registry_queue_init::<State>(&conn)?;
+        let qh = queue.handle();
+
+        let manager: ZwlrOutputManagerV1 = globals.bind(&qh, 1..=4, ())?;
+        let mut state = State {
+            manager: Some(manager),
+            ..Default::default()
+        };
+
+        // The compositor sends one `head` event per connected output followed by a single
+        // `done`, so a couple of round trips is enough to have a complete, consistent snapshot.
+        while !state.done {
+            queue.blocking_dispatch(&mut state)?;
+        }
+
+        state
+            .heads
+            .into_values()
+            .filter(|head| head.enabled)
+            .map(|head| head.into_monitor(&state.modes))
+            .collect()
+    }
+}
+
+#[derive(Default)]
+struct ModeInfo {
+    width: i32,
+    height: i32,
+    refresh_mhz: i32,
+}
+
+#[derive(Default)]
+struct HeadInfo {
+    name: String,
+    x: i32,
+    y: i32,
+    width_mm: i32,
+    height_mm: i32,
+    enabled: bool,
+    transform_rotated: bool,
+    current_mode: Option<ZwlrOutputModeV1>,
+}
+
+impl HeadInfo {
+    /// Converts the raw protocol data gathered for a head into the shared `Monitor` type. The
+    /// portrait/landscape swap mirrors what RandR does for a rotated CRTC.
+    fn into_monitor(self, modes: &HashMap<ZwlrOutputModeV1, ModeInfo>) -> Result<Monitor, anyhow::Error> {
+        let mode = self
+            .current_mode
+            .as_ref()
+            .and_then(|mode| modes.get(mode))
+            .ok_or_else(|| anyhow::Error::msg("output head has no current mode"))?;
+
+        let (width, height) = if self.transform_rotated {
+            (mode.height as u32, mode.width as u32)
+        } else {
+            (mode.width as u32, mode.height as u32)
+        };
+
+        // PhysicalSize is reported in the head's natural (unrotated) orientation, so swap it
+        // the same way `width`/`height` were swapped above, otherwise a rotated monitor's DPI
+        // would be computed against the wrong physical axis.
+        let width_mm = if self.transform_rotated {
+            self.height_mm
+        } else {
+            self.width_mm
+        };
+
+        let dpi = if width_mm > 0 {
+            width as f64 / (width_mm as f64 / 25.4)
+        } else {
+            0.0
+        };
+
+        Ok(Monitor {
+            x: self.x as i16,
+            y: self.y as i16,
+            width,
+            height,
+            // Wayland has no protocol-level notion of a "primary" monitor.
+            primary: false,
+            name: self.name,
+            refresh: mode.refresh_mhz as f64 / 1000.0,
+            dpi,
+        })
+    }
+}
+
+#[derive(Default)]
+struct State {
+    manager: Option<ZwlrOutputManagerV1>,
+    heads: HashMap<ZwlrOutputHeadV1, HeadInfo>,
+    modes: HashMap<ZwlrOutputModeV1, ModeInfo>,
+    done: bool,
+}
+
+impl Dispatch<wl_registry::WlRegistry, GlobalListContents> for State {
+    fn event(
+        _state: &mut Self,
+        _registry: &wl_registry::WlRegistry,
+        _event: wl_registry::Event,
+        _data: &GlobalListContents,
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        // `GlobalListContents` already collects the global list for us; nothing to do here.
+    }
+}
+
+impl Dispatch<ZwlrOutputManagerV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        _manager: &ZwlrOutputManagerV1,
+        event: zwlr_output_manager_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        match event {
+            zwlr_output_manager_v1::Event::Head(head) => {
+                state.heads.insert(head, HeadInfo::default());
+            }
+            zwlr_output_manager_v1::Event::Done { .. } => state.done = true,
+            zwlr_output_manager_v1::Event::Finished => state.done = true,
+            _ => {}
+        }
+    }
+
+    // The `head` event carries a `new_id` for a `zwlr_output_head_v1` the compositor just
+    // created; wayland-client needs to know what data/Dispatch impl to attach to it before
+    // `event` above runs, or it panics.
+    fn event_created_child(opcode: u16, qh: &QueueHandle<Self>) -> Arc<dyn ObjectData> {
+        match opcode {
+            zwlr_output_manager_v1::EVT_HEAD_OPCODE => qh.make_data::<ZwlrOutputHeadV1, ()>(()),
+            _ => unreachable!("zwlr_output_manager_v1 has no other new_id events, got opcode {opcode}"),
+        }
+    }
+}
+
+impl Dispatch<ZwlrOutputHeadV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        head: &ZwlrOutputHeadV1,
+        event: zwlr_output_head_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let Some(info) = state.heads.get_mut(head) else {
+            return;
+        };
+
+        match event {
+            zwlr_output_head_v1::Event::Name { name } => info.name = name,
+            zwlr_output_head_v1::Event::Position { x, y } => {
+                info.x = x;
+                info.y = y;
+            }
+            zwlr_output_head_v1::Event::PhysicalSize { width, height } => {
+                info.width_mm = width;
+                info.height_mm = height;
+            }
+            zwlr_output_head_v1::Event::Enabled { enabled } => info.enabled = enabled != 0,
+            zwlr_output_head_v1::Event::Transform { transform } => {
+                info.transform_rotated = matches!(
+                    transform.into_result(),
+                    Ok(wayland_client::protocol::wl_output::Transform::_90)
+                        | Ok(wayland_client::protocol::wl_output::Transform::_270)
+                        | Ok(wayland_client::protocol::wl_output::Transform::Flipped90)
+                        | Ok(wayland_client::protocol::wl_output::Transform::Flipped270)
+                );
+            }
+            zwlr_output_head_v1::Event::CurrentMode { mode } => info.current_mode = Some(mode),
+            _ => {}
+        }
+    }
+
+    // The `mode` event carries a `new_id` for a `zwlr_output_mode_v1` the compositor just
+    // created; see the comment on `ZwlrOutputManagerV1`'s `event_created_child` above.
+    fn event_created_child(opcode: u16, qh: &QueueHandle<Self>) -> Arc<dyn ObjectData> {
+        match opcode {
+            zwlr_output_head_v1::EVT_MODE_OPCODE => qh.make_data::<ZwlrOutputModeV1, ()>(()),
+            _ => unreachable!("zwlr_output_head_v1 has no other new_id events, got opcode {opcode}"),
+        }
+    }
+}
+
+impl Dispatch<ZwlrOutputModeV1, ()> for State {
+    fn event(
+        state: &mut Self,
+        mode: &ZwlrOutputModeV1,
+        event: zwlr_output_mode_v1::Event,
+        _data: &(),
+        _conn: &Connection,
+        _qh: &QueueHandle<Self>,
+    ) {
+        let info = state.modes.entry(mode.clone()).or_default();
+
+        match event {
+            zwlr_output_mode_v1::Event::Size { width, height } => {
+                info.width = width;
+                info.height = height;
+            }
+            zwlr_output_mode_v1::Event::Refresh { refresh } => info.refresh_mhz = refresh,
+            _ => {}
+        }
+    }
+}
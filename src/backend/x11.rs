@@ -0,0 +1,173 @@
+use std::collections::HashMap;
+
+use xcb::{self, randr, randr::MonitorInfo, x};
+
+use super::Backend;
+use crate::monitor::Monitor;
+use crate::{reconcile_children, ChildKey, Config};
+
+/// Enumerates monitors via RandR over XCB.
+pub(crate) struct X11Backend;
+
+impl Backend for X11Backend {
+    fn monitors(&self) -> Result<Vec<Monitor>, anyhow::Error> {
+        get_monitors()
+    }
+}
+
+impl TryFrom<&MonitorInfo> for Monitor {
+    type Error = anyhow::Error;
+
+    fn try_from(m: &MonitorInfo) -> Result<Self, Self::Error> {
+        let (conn, _) = xcb::Connection::connect(None)?;
+        let cookie = conn.send_request(&x::GetAtomName {
+            atom: m.name().to_owned(),
+        });
+        let reply: x::GetAtomNameReply = conn.wait_for_reply(cookie)?;
+        // The name is Latin-1 encoded. Latin-1 codepoints are UTF-8 compatible,
+        // but Latin-1 encoding is not.
+        let as_str = reply.name().as_bytes().iter().map(|&c| c as char).collect();
+
+        let (refresh, dpi) = match m.outputs().next() {
+            Some(&output) => refresh_and_dpi(&conn, output, m.width().into())?,
+            None => (0.0, 0.0),
+        };
+
+        Ok(Monitor {
+            x: m.x(),
+            y: m.y(),
+            width: m.width().into(),
+            height: m.height().into(),
+            name: as_str,
+            primary: m.primary(),
+            refresh,
+            dpi,
+        })
+    }
+}
+
+/// Computes the active refresh rate (Hz) and DPI of `output` on `conn`.
+///
+/// The refresh rate is derived from the active mode's dot clock and total horizontal/vertical
+/// scan lines (`dot_clock / (htotal * vtotal)`); DPI is derived from the output's physical size
+/// in millimeters and `width_px`, the monitor's pixel width.
+fn refresh_and_dpi(
+    conn: &xcb::Connection,
+    output: randr::Output,
+    width_px: u32,
+) -> Result<(f64, f64), anyhow::Error> {
+    let output_cookie = conn.send_request(&randr::GetOutputInfo {
+        output,
+        config_timestamp: 0,
+    });
+    let output_info: randr::GetOutputInfoReply = conn.wait_for_reply(output_cookie)?;
+
+    let dpi = if output_info.mm_width() > 0 {
+        width_px as f64 / (output_info.mm_width() as f64 / 25.4)
+    } else {
+        0.0
+    };
+
+    let crtc = output_info.crtc();
+    let refresh = if crtc.resource_id() == 0 {
+        0.0
+    } else {
+        let crtc_cookie = conn.send_request(&randr::GetCrtcInfo {
+            crtc,
+            config_timestamp: 0,
+        });
+        let crtc_info: randr::GetCrtcInfoReply = conn.wait_for_reply(crtc_cookie)?;
+
+        let setup = conn.get_setup();
+        let root = setup
+            .roots()
+            .next()
+            .ok_or_else(|| anyhow::Error::msg("no screen available"))?
+            .root();
+        let resources_cookie = conn.send_request(&randr::GetScreenResources { window: root });
+        let resources: randr::GetScreenResourcesReply = conn.wait_for_reply(resources_cookie)?;
+
+        // `htotal`/`vtotal` are the field names straight from the RandR protocol's ModeInfo
+        // struct (xcbproto's randr.xml; mirrored by libxcb's xcb_randr_mode_info_t) - they're
+        // single protocol tokens, not a "total" split across two words, so xcb's codegen
+        // doesn't introduce an underscore here.
+        resources
+            .modes()
+            .iter()
+            .find(|mode| mode.id == crtc_info.mode())
+            .filter(|mode| mode.htotal > 0 && mode.vtotal > 0)
+            .map(|mode| mode.dot_clock as f64 / (mode.htotal as f64 * mode.vtotal as f64))
+            .unwrap_or(0.0)
+    };
+
+    Ok((refresh, dpi))
+}
+
+fn get_connection() -> Result<(xcb::Connection, x::Window), anyhow::Error> {
+    let (conn, screen_num) = xcb::Connection::connect(None)?;
+
+    // TODO: use conn.active_extensions() to check for randr https://docs.rs/xcb/latest/xcb/struct.Connection.html#method.active_extensions
+
+    let setup = conn.get_setup();
+    let screen = setup.roots().nth(screen_num as usize).unwrap();
+    let window: x::Window = conn.generate_id();
+    let cookie = conn.send_request_checked(&x::CreateWindow {
+        depth: x::COPY_FROM_PARENT as u8,
+        wid: window,
+        parent: screen.root(),
+        x: 0,
+        y: 0,
+        width: 1,
+        height: 1,
+        border_width: 0,
+        class: x::WindowClass::InputOutput,
+        visual: screen.root_visual(),
+        value_list: &[x::Cw::BackPixel(screen.white_pixel())],
+    });
+    conn.check_request(cookie)?;
+
+    Ok((conn, window))
+}
+
+fn get_monitors() -> Result<Vec<Monitor>, anyhow::Error> {
+    let (conn, window) = get_connection()?;
+    let cookie = conn.send_request(&randr::GetMonitors {
+        window,
+        get_active: false,
+    });
+    let monitor_reply: randr::GetMonitorsReply = conn.wait_for_reply(cookie)?;
+    let monitors: Result<Vec<Monitor>, anyhow::Error> =
+        monitor_reply.monitors().map(|m| m.try_into()).collect();
+
+    monitors
+}
+
+/// Stays resident, re-running `reconcile_children` for every config whenever RandR reports a
+/// screen change (monitor plugged/unplugged, resolution change, rotation).
+pub(crate) fn watch(
+    configs: &[Config],
+    dry_run: bool,
+    mut children: HashMap<ChildKey, std::process::Child>,
+) -> Result<(), anyhow::Error> {
+    let (conn, _window) = get_connection()?;
+    let setup = conn.get_setup();
+    let root = setup
+        .roots()
+        .next()
+        .ok_or_else(|| anyhow::Error::msg("no screen available"))?
+        .root();
+
+    conn.send_request(&randr::SelectInput {
+        window: root,
+        enable: randr::NotifyMask::SCREEN_CHANGE,
+    });
+    conn.flush()?;
+
+    loop {
+        let event = conn.wait_for_event()?;
+        if let xcb::Event::RandR(randr::Event::ScreenChangeNotify(_)) = event {
+            let monitors = get_monitors()?;
+            reconcile_children(configs, &monitors, dry_run, &mut children)?;
+        }
+    }
+}
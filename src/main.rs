@@ -1,10 +1,16 @@
 use clap::{Args, Parser};
 use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::path::PathBuf;
-use xcb::{self, randr, randr::MonitorInfo, x};
 
-#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+mod backend;
+mod monitor;
+
+use backend::{Backend, BackendKind};
+use monitor::Monitor;
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
 #[serde(rename_all = "lowercase")]
 enum Affinity {
     Primary,
@@ -17,6 +23,81 @@ enum Affinity {
     Bottommost,
     Portrait,
     Landscape,
+    HighestRefresh,
+    LowestRefresh,
+    HighestDpi,
+    LowestDpi,
+    /// Matches a monitor whose connector name is exactly the given value (case-insensitive).
+    Name(String),
+    /// Matches a monitor whose connector name starts with the given value (case-insensitive).
+    Prefix(String),
+    /// Matches the monitors whose connector name contains the given value as a case-insensitive
+    /// subsequence with the highest `fuzzy_score`; lower-scoring matches are not selected.
+    Fuzzy(String),
+}
+
+impl Affinity {
+    /// Whether `monitor` is selected by this name-based affinity. Only meaningful for
+    /// `Name`/`Prefix`; returns `false` for geometric and fuzzy affinities (`Fuzzy` is ranked
+    /// by `fuzzy_score` rather than matched as a plain boolean — see `get_monitors_for_affinities`).
+    fn name_matches(&self, monitor: &Monitor) -> bool {
+        match self {
+            Affinity::Name(name) => monitor.name.eq_ignore_ascii_case(name),
+            Affinity::Prefix(prefix) => monitor
+                .name
+                .to_lowercase()
+                .starts_with(&prefix.to_lowercase()),
+            _ => false,
+        }
+    }
+}
+
+/// Scores a case-insensitive subsequence match of `query` against `candidate`.
+///
+/// Returns `None` if `query` is not a subsequence of `candidate`. Otherwise returns a score
+/// that rewards consecutive matched characters and matches that fall at the start of the
+/// name or just after a separator (`-`, `_`, space, or a digit/letter boundary), so that e.g.
+/// `lg34` prefers `LG-ULTRAGEAR-34` over a match scattered across unrelated characters.
+fn fuzzy_score(query: &str, candidate: &str) -> Option<u32> {
+    let query: Vec<char> = query.to_lowercase().chars().collect();
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+
+    if query.is_empty() {
+        return Some(0);
+    }
+
+    let mut score = 0u32;
+    let mut query_index = 0;
+    let mut prev_match_index: Option<usize> = None;
+
+    for (candidate_index, &c) in candidate.iter().enumerate() {
+        if query_index >= query.len() {
+            break;
+        }
+        if c != query[query_index] {
+            continue;
+        }
+
+        score += 1;
+        if candidate_index > 0 && prev_match_index == Some(candidate_index - 1) {
+            score += 3;
+        }
+        if candidate_index == 0
+            || matches!(candidate[candidate_index - 1], '-' | ' ' | '_')
+            || candidate[candidate_index - 1].is_ascii_digit() != c.is_ascii_digit()
+        {
+            score += 2;
+        }
+
+        prev_match_index = Some(candidate_index);
+        query_index += 1;
+    }
+
+    if query_index == query.len() {
+        Some(score)
+    } else {
+        None
+    }
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -26,20 +107,26 @@ impl std::str::FromStr for AffinityPair {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let parts: Vec<&str> = s.split("-").collect();
-        if parts.len() == 1 {
-            Ok(AffinityPair(
-                toml::Value::String(parts[0].into()).try_into()?,
-                true,
-            ))
-        } else if parts[0] != "not" {
-            Err(anyhow::Error::msg("invalid affinity"))
+        // The parameterized forms (`name=`, `prefix=`, `fuzzy=`) use `=` to separate the
+        // matcher from its argument, so we peel off a leading `not-` before looking for it;
+        // otherwise `not-prefix=DP` would split into the unknown key `not-prefix`.
+        let (inclusive, rest) = match s.strip_prefix("not-") {
+            Some(rest) => (false, rest),
+            None => (true, s),
+        };
+
+        let affinity = if let Some((key, value)) = rest.split_once('=') {
+            match key {
+                "name" => Affinity::Name(value.to_string()),
+                "prefix" => Affinity::Prefix(value.to_string()),
+                "fuzzy" => Affinity::Fuzzy(value.to_string()),
+                _ => return Err(anyhow::Error::msg("invalid affinity")),
+            }
         } else {
-            Ok(AffinityPair(
-                toml::Value::String(parts[1].into()).try_into()?,
-                false,
-            ))
-        }
+            toml::Value::String(rest.into()).try_into()?
+        };
+
+        Ok(AffinityPair(affinity, inclusive))
     }
 }
 
@@ -55,11 +142,11 @@ impl<'de> serde::Deserialize<'de> for AffinityPair {
 }
 
 #[derive(Debug, Deserialize, Args)]
-struct Config {
+pub(crate) struct Config {
     /// The command to execute with monitor affinity.
     cmd: String,
-    /// Arguments to pass to the command. %s will be replaced with the name of the preferred
-    /// monitor.
+    /// Arguments to pass to the command. %s, %x, %y, %w, %h, %p, and %i are replaced with the
+    /// preferred monitor's name, x, y, width, height, primary flag, and selection index.
     args: Option<Vec<String>>,
     /// One or more monitor affinities, evaluated in order to select preferred monitor.
     #[arg(short, long = "affinity", required = true)]
@@ -69,13 +156,50 @@ struct Config {
     #[arg(short = 'm', long, default_value_t = false)]
     #[serde(default)]
     allow_multiple: bool,
-    /// Set an env var to the name of the preferred monitor.
+    /// Set an env var to the name of the preferred monitor, along with companion
+    /// MONITOR_X/MONITOR_Y/MONITOR_WIDTH/MONITOR_HEIGHT/MONITOR_PRIMARY vars describing its
+    /// geometry.
     #[arg(short, long)]
     env: Option<String>,
 }
 
+/// Expands the `%s/%x/%y/%w/%h/%p/%i` placeholders in `template` against `monitor` in a single
+/// scan, so a substituted value (e.g. a connector name that happens to contain `%x`) never gets
+/// rescanned by a later substitution the way a chain of `str::replace` calls would.
+fn substitute_placeholders(template: &str, monitor: &Monitor, index: usize) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+
+        match chars.peek() {
+            Some('s') => out.push_str(&monitor.name),
+            Some('x') => out.push_str(&monitor.x.to_string()),
+            Some('y') => out.push_str(&monitor.y.to_string()),
+            Some('w') => out.push_str(&monitor.width.to_string()),
+            Some('h') => out.push_str(&monitor.height.to_string()),
+            Some('p') => out.push_str(&monitor.primary.to_string()),
+            Some('i') => out.push_str(&index.to_string()),
+            _ => {
+                out.push(c);
+                continue;
+            }
+        }
+        chars.next();
+    }
+
+    out
+}
+
 impl Config {
-    fn get_commands_for_monitors(&self, monitors: &[Monitor]) -> Vec<std::process::Command> {
+    /// Returns each matched monitor paired with the command that would be run for it, in the
+    /// same order `get_commands_for_monitors` would return the commands themselves. Watch mode
+    /// uses the monitor half of the pair to track which child belongs to which monitor.
+    fn get_monitor_commands(&self, monitors: &[Monitor]) -> Vec<(Monitor, std::process::Command)> {
         let monitors = get_monitors_for_affinities(&self.affinities, monitors);
         let mut ret = Vec::new();
         if !monitors.is_empty() {
@@ -85,20 +209,35 @@ impl Config {
                 1
             };
 
-            for monitor in monitors.iter().take(max) {
+            for (index, monitor) in monitors.into_iter().take(max).enumerate() {
                 let mut cmd = std::process::Command::new(&self.cmd);
                 if let Some(args) = &self.args {
-                    cmd.args(args.iter().map(|s| s.replace("%s", &monitor.name)));
+                    cmd.args(
+                        args.iter()
+                            .map(|s| substitute_placeholders(s, &monitor, index)),
+                    );
                 }
                 if let Some(env) = &self.env {
                     cmd.env(env, &monitor.name);
+                    cmd.env("MONITOR_X", monitor.x.to_string());
+                    cmd.env("MONITOR_Y", monitor.y.to_string());
+                    cmd.env("MONITOR_WIDTH", monitor.width.to_string());
+                    cmd.env("MONITOR_HEIGHT", monitor.height.to_string());
+                    cmd.env("MONITOR_PRIMARY", monitor.primary.to_string());
                 }
-                ret.push(cmd);
+                ret.push((monitor, cmd));
             }
         }
 
         ret
     }
+
+    fn get_commands_for_monitors(&self, monitors: &[Monitor]) -> Vec<std::process::Command> {
+        self.get_monitor_commands(monitors)
+            .into_iter()
+            .map(|(_, cmd)| cmd)
+            .collect()
+    }
 }
 
 #[derive(Parser)]
@@ -107,6 +246,15 @@ struct CliConfig {
     /// Print what commands would be run, but don't run them.
     #[arg(short, long, default_value_t = false)]
     dry_run: bool,
+    /// Stay resident and re-evaluate affinities whenever the monitor layout changes, instead of
+    /// exiting after the first pass. Only the X11 backend supports this; with the Wayland
+    /// backend the first pass runs and the program then exits with an error.
+    #[arg(short, long, default_value_t = false)]
+    watch: bool,
+    /// Force which display backend to use instead of autodetecting it from the session
+    /// (WAYLAND_DISPLAY vs DISPLAY).
+    #[arg(long)]
+    backend: Option<BackendKind>,
     #[command(flatten)]
     cli_config: Option<Config>,
     /// Read configuration from a TOML file. Required for running more than one command.
@@ -119,40 +267,6 @@ struct ConfigFile {
     config: Vec<Config>,
 }
 
-#[derive(Clone, Debug)]
-struct Monitor {
-    width: u32,
-    height: u32,
-    x: i16,
-    y: i16,
-    primary: bool,
-    name: String,
-}
-
-impl TryFrom<&MonitorInfo> for Monitor {
-    type Error = anyhow::Error;
-
-    fn try_from(m: &MonitorInfo) -> Result<Self, Self::Error> {
-        let (conn, _) = xcb::Connection::connect(None)?;
-        let cookie = conn.send_request(&x::GetAtomName {
-            atom: m.name().to_owned(),
-        });
-        let reply: x::GetAtomNameReply = conn.wait_for_reply(cookie)?;
-        // The name is Latin-1 encoded. Latin-1 codepoints are UTF-8 compatible,
-        // but Latin-1 encoding is not.
-        let as_str = reply.name().as_bytes().iter().map(|&c| c as char).collect();
-
-        Ok(Monitor {
-            x: m.x(),
-            y: m.y(),
-            width: m.width().into(),
-            height: m.height().into(),
-            name: as_str,
-            primary: m.primary(),
-        })
-    }
-}
-
 fn get_monitors_for_affinities(affinities: &[AffinityPair], monitors: &[Monitor]) -> Vec<Monitor> {
     let mut monitors = monitors.to_owned();
 
@@ -174,22 +288,54 @@ fn get_monitors_for_affinities(affinities: &[AffinityPair], monitors: &[Monitor]
                     }
                 });
             }
+            Affinity::Name(_) | Affinity::Prefix(_) => {
+                monitors.retain(|m| {
+                    if *inclusive {
+                        affinity.name_matches(m)
+                    } else {
+                        !affinity.name_matches(m)
+                    }
+                });
+            }
+            Affinity::Fuzzy(query) => {
+                // Unlike Name/Prefix, a fuzzy match isn't pass/fail: among the monitors that
+                // match at all, prefer the tightest one (consecutive runs, boundary starts),
+                // the same way Largest/Smallest prefer an extreme over any monitor that merely
+                // qualifies.
+                if *inclusive {
+                    let best = monitors
+                        .iter()
+                        .filter_map(|m| fuzzy_score(query, &m.name))
+                        .max();
+                    monitors.retain(|m| best.is_some() && fuzzy_score(query, &m.name) == best);
+                } else {
+                    monitors.retain(|m| fuzzy_score(query, &m.name).is_none());
+                }
+            }
             Affinity::Largest
             | Affinity::Smallest
             | Affinity::Leftmost
             | Affinity::Rightmost
             | Affinity::Topmost
-            | Affinity::Bottommost => {
-                let key_func = match affinity {
-                    Affinity::Largest => |a: &Monitor| -((a.width * a.height) as i64),
-                    Affinity::Smallest => |a: &Monitor| ((a.width * a.height) as i64),
-                    Affinity::Rightmost => |a: &Monitor| -(a.x as i64),
-                    Affinity::Leftmost => |a: &Monitor| a.x as i64,
-                    Affinity::Topmost => |a: &Monitor| -(a.y as i64),
-                    Affinity::Bottommost => |a: &Monitor| a.y as i64,
-                    _ => |_: &Monitor| 0i64,
+            | Affinity::Bottommost
+            | Affinity::HighestRefresh
+            | Affinity::LowestRefresh
+            | Affinity::HighestDpi
+            | Affinity::LowestDpi => {
+                let key_func: fn(&Monitor) -> f64 = match affinity {
+                    Affinity::Largest => |a: &Monitor| -((a.width * a.height) as f64),
+                    Affinity::Smallest => |a: &Monitor| (a.width * a.height) as f64,
+                    Affinity::Rightmost => |a: &Monitor| -(a.x as f64),
+                    Affinity::Leftmost => |a: &Monitor| a.x as f64,
+                    Affinity::Topmost => |a: &Monitor| -(a.y as f64),
+                    Affinity::Bottommost => |a: &Monitor| a.y as f64,
+                    Affinity::HighestRefresh => |a: &Monitor| -a.refresh,
+                    Affinity::LowestRefresh => |a: &Monitor| a.refresh,
+                    Affinity::HighestDpi => |a: &Monitor| -a.dpi,
+                    Affinity::LowestDpi => |a: &Monitor| a.dpi,
+                    _ => |_: &Monitor| 0.0,
                 };
-                monitors.sort_unstable_by_key(key_func);
+                monitors.sort_unstable_by(|a, b| key_func(a).partial_cmp(&key_func(b)).unwrap());
 
                 if monitors.len() > 1 {
                     let first = key_func(&monitors[0]);
@@ -216,43 +362,55 @@ fn get_monitors_for_affinities(affinities: &[AffinityPair], monitors: &[Monitor]
     monitors
 }
 
-fn get_connection() -> Result<(xcb::Connection, x::Window), anyhow::Error> {
-    let (conn, screen_num) = xcb::Connection::connect(None)?;
-
-    // TODO: use conn.active_extensions() to check for randr https://docs.rs/xcb/latest/xcb/struct.Connection.html#method.active_extensions
-
-    let setup = conn.get_setup();
-    let screen = setup.roots().nth(screen_num as usize).unwrap();
-    let window: x::Window = conn.generate_id();
-    let cookie = conn.send_request_checked(&x::CreateWindow {
-        depth: x::COPY_FROM_PARENT as u8,
-        wid: window,
-        parent: screen.root(),
-        x: 0,
-        y: 0,
-        width: 1,
-        height: 1,
-        border_width: 0,
-        class: x::WindowClass::InputOutput,
-        visual: screen.root_visual(),
-        value_list: &[x::Cw::BackPixel(screen.white_pixel())],
-    });
-    conn.check_request(cookie)?;
-
-    Ok((conn, window))
-}
+/// Identifies a single spawned child: which config (by index into the slice passed to
+/// `reconcile_children`) launched it, and for which monitor. Keying on the pair (rather than
+/// just the monitor name) lets two different configs both target the same monitor, each with
+/// its own child.
+pub(crate) type ChildKey = (usize, String);
+
+/// Spawns commands for newly-matching (config, monitor) pairs and kills children whose pair no
+/// longer matches. `children` is updated in place so the same map can be threaded through
+/// repeated calls in watch mode.
+pub(crate) fn reconcile_children(
+    configs: &[Config],
+    monitors: &[Monitor],
+    dry_run: bool,
+    children: &mut HashMap<ChildKey, std::process::Child>,
+) -> Result<(), anyhow::Error> {
+    let mut wanted = HashSet::new();
+
+    for (config_index, c) in configs.iter().enumerate() {
+        for (monitor, mut cmd) in c.get_monitor_commands(monitors) {
+            let key = (config_index, monitor.name.clone());
+            wanted.insert(key.clone());
+            if children.contains_key(&key) {
+                continue;
+            }
+            if dry_run {
+                println!("{:?}", cmd);
+            } else {
+                children.insert(key, cmd.spawn()?);
+            }
+        }
+    }
 
-fn get_monitors() -> Result<Vec<Monitor>, anyhow::Error> {
-    let (conn, window) = get_connection()?;
-    let cookie = conn.send_request(&randr::GetMonitors {
-        window,
-        get_active: false,
-    });
-    let monitor_reply: randr::GetMonitorsReply = conn.wait_for_reply(cookie)?;
-    let monitors: Result<Vec<Monitor>, anyhow::Error> =
-        monitor_reply.monitors().map(|m| m.try_into()).collect();
+    for (key, child) in children.iter_mut() {
+        if !wanted.contains(key) {
+            // A child that already exited on its own (e.g. ESRCH) shouldn't take the whole
+            // watch loop down with it; log and keep reconciling the rest.
+            if let Err(err) = child.kill() {
+                eprintln!("failed to kill child for {:?}: {err}", key);
+            }
+            // kill() doesn't reap the process; without wait() the zombie entry sticks around
+            // for as long as the watcher runs.
+            if let Err(err) = child.wait() {
+                eprintln!("failed to reap child for {:?}: {err}", key);
+            }
+        }
+    }
+    children.retain(|key, _| wanted.contains(key));
 
-    monitors
+    Ok(())
 }
 
 fn main() -> Result<(), anyhow::Error> {
@@ -268,15 +426,20 @@ fn main() -> Result<(), anyhow::Error> {
         configs.extend(config_file.config);
     }
 
-    let monitors = get_monitors()?;
+    let backend_kind = conf.backend.unwrap_or_else(BackendKind::detect);
+    let backend = backend_kind.build();
+    let monitors = backend.monitors()?;
 
-    for c in configs.iter() {
-        let commands = c.get_commands_for_monitors(&monitors);
-        for mut cmd in commands.into_iter() {
-            if conf.dry_run {
-                println!("{:?}", cmd);
-            } else {
-                cmd.spawn()?;
+    let mut children = HashMap::new();
+    reconcile_children(&configs, &monitors, conf.dry_run, &mut children)?;
+
+    if conf.watch {
+        match backend_kind {
+            BackendKind::X11 => backend::watch_x11(&configs, conf.dry_run, children)?,
+            BackendKind::Wayland => {
+                return Err(anyhow::Error::msg(
+                    "--watch is not yet supported with the Wayland backend",
+                ))
             }
         }
     }
@@ -296,6 +459,8 @@ mod test {
             height: 1080,
             primary: true,
             name: "PRIMARY".into(),
+            refresh: 60.0,
+            dpi: 96.0,
         }
     }
 
@@ -307,6 +472,8 @@ mod test {
             height: 1440,
             primary: false,
             name: "LARGE".into(),
+            refresh: 60.0,
+            dpi: 96.0,
         }
     }
     fn top() -> Monitor {
@@ -317,6 +484,8 @@ mod test {
             height: 768,
             primary: false,
             name: "TOP".into(),
+            refresh: 60.0,
+            dpi: 96.0,
         }
     }
 
@@ -328,6 +497,34 @@ mod test {
             height: 1024,
             primary: false,
             name: "PORTRAIT".into(),
+            refresh: 60.0,
+            dpi: 96.0,
+        }
+    }
+
+    fn high_refresh() -> Monitor {
+        Monitor {
+            x: 0,
+            y: 0,
+            width: 2560,
+            height: 1440,
+            primary: false,
+            name: "GAMING".into(),
+            refresh: 144.0,
+            dpi: 96.0,
+        }
+    }
+
+    fn high_dpi() -> Monitor {
+        Monitor {
+            x: 0,
+            y: 0,
+            width: 3840,
+            height: 2160,
+            primary: false,
+            name: "LAPTOP".into(),
+            refresh: 60.0,
+            dpi: 220.0,
         }
     }
 
@@ -349,6 +546,42 @@ mod test {
         assert_eq!("PRIMARY", selected_monitors[0].name);
     }
 
+    #[test]
+    fn test_affinities_highest_refresh() {
+        let monitors = vec![primary(), high_refresh()];
+        let affinities = vec![AffinityPair(Affinity::HighestRefresh, true)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("GAMING", selected_monitors[0].name);
+    }
+
+    #[test]
+    fn test_affinities_lowest_refresh() {
+        let monitors = vec![primary(), high_refresh()];
+        let affinities = vec![AffinityPair(Affinity::LowestRefresh, true)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("PRIMARY", selected_monitors[0].name);
+    }
+
+    #[test]
+    fn test_affinities_highest_dpi() {
+        let monitors = vec![primary(), high_dpi()];
+        let affinities = vec![AffinityPair(Affinity::HighestDpi, true)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("LAPTOP", selected_monitors[0].name);
+    }
+
+    #[test]
+    fn test_affinities_lowest_dpi() {
+        let monitors = vec![primary(), high_dpi()];
+        let affinities = vec![AffinityPair(Affinity::LowestDpi, true)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("PRIMARY", selected_monitors[0].name);
+    }
+
     #[test]
     fn test_affinities_primary() {
         let monitors = vec![large(), primary()];
@@ -498,6 +731,107 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_affinity_pair_parsing_name_forms() {
+        assert_eq!(
+            AffinityPair(Affinity::Name("DP-1".into()), true),
+            "name=DP-1".parse().unwrap()
+        );
+        assert_eq!(
+            AffinityPair(Affinity::Prefix("HDMI".into()), true),
+            "prefix=HDMI".parse().unwrap()
+        );
+        assert_eq!(
+            AffinityPair(Affinity::Fuzzy("lg34".into()), true),
+            "fuzzy=lg34".parse().unwrap()
+        );
+        assert_eq!(
+            AffinityPair(Affinity::Prefix("DP".into()), false),
+            "not-prefix=DP".parse().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_affinity_pair_parsing_name_form_invalid_key() {
+        let result: Result<AffinityPair, _> = "bogus=DP-1".parse();
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_affinities_name_exact() {
+        let monitors = vec![primary(), large()];
+        let affinities = vec![AffinityPair(Affinity::Name("large".into()), true)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("LARGE", selected_monitors[0].name);
+    }
+
+    #[test]
+    fn test_affinities_prefix() {
+        let monitors = vec![primary(), large(), top()];
+        let affinities = vec![AffinityPair(Affinity::Prefix("PRI".into()), true)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("PRIMARY", selected_monitors[0].name);
+    }
+
+    #[test]
+    fn test_affinities_prefix_negated() {
+        let monitors = vec![primary(), large()];
+        let affinities = vec![AffinityPair(Affinity::Prefix("PRI".into()), false)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("LARGE", selected_monitors[0].name);
+    }
+
+    #[test]
+    fn test_affinities_fuzzy() {
+        let mut lg = large();
+        lg.name = "LG-ULTRAGEAR-34".into();
+        let monitors = vec![primary(), lg];
+        let affinities = vec![AffinityPair(Affinity::Fuzzy("lg34".into()), true)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("LG-ULTRAGEAR-34", selected_monitors[0].name);
+    }
+
+    #[test]
+    fn test_affinities_fuzzy_prefers_tighter_match() {
+        let mut tight = large();
+        tight.name = "LG-ULTRAGEAR-34".into();
+        let mut scattered = top();
+        scattered.name = "L1G2U3L4TRAGEAR".into();
+        let monitors = vec![scattered, tight];
+        let affinities = vec![AffinityPair(Affinity::Fuzzy("lg34".into()), true)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("LG-ULTRAGEAR-34", selected_monitors[0].name);
+    }
+
+    #[test]
+    fn test_affinities_fuzzy_negated_excludes_any_match() {
+        let mut lg = large();
+        lg.name = "LG-ULTRAGEAR-34".into();
+        let monitors = vec![primary(), lg];
+        let affinities = vec![AffinityPair(Affinity::Fuzzy("lg34".into()), false)];
+        let selected_monitors = get_monitors_for_affinities(&affinities, &monitors);
+        assert_eq!(1, selected_monitors.len());
+        assert_eq!("PRIMARY", selected_monitors[0].name);
+    }
+
+    #[test]
+    fn test_fuzzy_score_requires_in_order_subsequence() {
+        assert!(fuzzy_score("lg34", "LG-ULTRAGEAR-34").is_some());
+        assert!(fuzzy_score("43gl", "LG-ULTRAGEAR-34").is_none());
+    }
+
+    #[test]
+    fn test_fuzzy_score_prefers_consecutive_and_boundary_matches() {
+        let tight = fuzzy_score("lg34", "LG-ULTRAGEAR-34").unwrap();
+        let scattered = fuzzy_score("lg34", "L1G2U3L4TRAGEAR").unwrap();
+        assert!(tight > scattered);
+    }
+
     #[test]
     fn test_affinities_matches_multiple_criteria() {
         let monitors = vec![primary(), top(), large(), portrait()];
@@ -524,10 +858,25 @@ mod test {
         assert_eq!(1, commands.len());
         assert_eq!(
             format!("{:?}", commands[0]),
-            r#"MONITOR="LARGE" "foobar" "baz""#
+            r#"MONITOR="LARGE" MONITOR_HEIGHT="1440" MONITOR_PRIMARY="false" MONITOR_WIDTH="3440" MONITOR_X="1920" MONITOR_Y="0" "foobar" "baz""#
         );
     }
 
+    #[test]
+    fn test_get_monitor_commands_pairs_monitor_with_command() {
+        let config = Config {
+            cmd: "foobar".into(),
+            args: Some(vec!["baz".into()]),
+            affinities: vec![AffinityPair(Affinity::Nonprimary, true)],
+            allow_multiple: true,
+            env: Some("MONITOR".into()),
+        };
+        let pairs = config.get_monitor_commands(&[top(), large()]);
+        assert_eq!(2, pairs.len());
+        assert_eq!("LARGE", pairs[0].0.name);
+        assert_eq!("TOP", pairs[1].0.name);
+    }
+
     #[test]
     fn test_get_commands_for_monitors_multiple() {
         let config = Config {
@@ -541,11 +890,81 @@ mod test {
         assert_eq!(2, commands.len());
         assert_eq!(
             format!("{:?}", commands[0]),
-            r#"MONITOR="LARGE" "foobar" "baz""#
+            r#"MONITOR="LARGE" MONITOR_HEIGHT="1440" MONITOR_PRIMARY="false" MONITOR_WIDTH="3440" MONITOR_X="1920" MONITOR_Y="0" "foobar" "baz""#
         );
         assert_eq!(
             format!("{:?}", commands[1]),
-            r#"MONITOR="TOP" "foobar" "baz""#
+            r#"MONITOR="TOP" MONITOR_HEIGHT="768" MONITOR_PRIMARY="false" MONITOR_WIDTH="1024" MONITOR_X="0" MONITOR_Y="1440" "foobar" "baz""#
+        );
+    }
+
+    #[test]
+    fn test_reconcile_children_two_configs_same_monitor_both_spawn() {
+        let configs = vec![
+            Config {
+                cmd: "true".into(),
+                args: None,
+                affinities: vec![AffinityPair(Affinity::Primary, true)],
+                allow_multiple: false,
+                env: None,
+            },
+            Config {
+                cmd: "true".into(),
+                args: None,
+                affinities: vec![AffinityPair(Affinity::Primary, true)],
+                allow_multiple: false,
+                env: None,
+            },
+        ];
+        let mut children = HashMap::new();
+        reconcile_children(&configs, &[primary()], false, &mut children).unwrap();
+
+        assert_eq!(2, children.len());
+        assert!(children.contains_key(&(0, "PRIMARY".to_string())));
+        assert!(children.contains_key(&(1, "PRIMARY".to_string())));
+    }
+
+    #[test]
+    fn test_reconcile_children_ok_when_child_already_exited() {
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        child.wait().unwrap();
+
+        let mut children = HashMap::new();
+        children.insert((0, "PRIMARY".to_string()), child);
+
+        // No configs match, so reconcile kills and reaps a child that has already been reaped;
+        // that must still return Ok and drop the entry.
+        let result = reconcile_children(&[], &[primary()], false, &mut children);
+        assert!(result.is_ok());
+        assert!(children.is_empty());
+    }
+
+    #[test]
+    fn test_get_commands_for_monitors_placeholder_substitution() {
+        let config = Config {
+            cmd: "foobar".into(),
+            args: Some(vec!["%s-%i".into(), "%x,%y".into(), "%wx%h".into(), "%p".into()]),
+            affinities: vec![AffinityPair(Affinity::Primary, true)],
+            allow_multiple: false,
+            env: None,
+        };
+        let commands = config.get_commands_for_monitors(&[primary(), large()]);
+        assert_eq!(1, commands.len());
+        assert_eq!(
+            format!("{:?}", commands[0]),
+            r#""foobar" "PRIMARY-0" "0,0" "1920x1080" "true""#
+        );
+    }
+
+    #[test]
+    fn test_substitute_placeholders_does_not_rescan_substituted_text() {
+        // If %s were substituted first and the result rescanned by later .replace() calls, the
+        // literal "%x" living inside this monitor's own name would get mangled too.
+        let mut odd = primary();
+        odd.name = "DP-%x-1".into();
+        assert_eq!(
+            "name=DP-%x-1 x=0",
+            substitute_placeholders("name=%s x=%x", &odd, 0)
         );
     }
 }
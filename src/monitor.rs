@@ -0,0 +1,14 @@
+/// A single monitor, as reported by whichever `Backend` enumerated it.
+#[derive(Clone, Debug)]
+pub(crate) struct Monitor {
+    pub(crate) width: u32,
+    pub(crate) height: u32,
+    pub(crate) x: i16,
+    pub(crate) y: i16,
+    pub(crate) primary: bool,
+    pub(crate) name: String,
+    /// Refresh rate of the monitor's active mode, in Hz.
+    pub(crate) refresh: f64,
+    /// Pixel density of the monitor, in dots per inch.
+    pub(crate) dpi: f64,
+}